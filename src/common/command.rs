@@ -1,5 +1,7 @@
+use std::fmt;
 use std::ops::Deref;
 
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::common::{
@@ -10,6 +12,30 @@ use crate::common::{
 };
 
 pub const MAGIC_ELEMENTID: &str = "element-6066-11e4-a52e-4f735466cecf";
+pub const MAGIC_SHADOWID: &str = "shadow-6066-11e4-a52e-4f735466cecf";
+
+/// Unique identifier for a shadow root, as returned by `GetElementShadowRoot`.
+///
+/// This is the shadow-tree analogue of `ElementId`, carrying the magic key
+/// defined by the WebDriver spec so that responses can be deserialized the
+/// same way elements are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ShadowId {
+    #[serde(rename = "shadow-6066-11e4-a52e-4f735466cecf")]
+    id: String,
+}
+
+impl fmt::Display for ShadowId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl From<String> for ShadowId {
+    fn from(id: String) -> Self {
+        ShadowId { id }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum RequestMethod {
@@ -40,11 +66,152 @@ impl RequestData {
     }
 }
 
-pub struct Actions(serde_json::Value);
+/// A pointer input source for `ActionSequence`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PointerType {
+    Mouse,
+    Pen,
+    Touch,
+}
+
+/// Where a `PointerAction::PointerMove` action is measured from.
+#[derive(Debug, Clone)]
+pub enum PointerOrigin<'a> {
+    Viewport,
+    Pointer,
+    Element(&'a ElementId),
+}
 
-impl From<serde_json::Value> for Actions {
+impl<'a> Serialize for PointerOrigin<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            PointerOrigin::Viewport => serializer.serialize_str("viewport"),
+            PointerOrigin::Pointer => serializer.serialize_str("pointer"),
+            PointerOrigin::Element(element_id) => {
+                json!({ MAGIC_ELEMENTID: element_id.to_string() }).serialize(serializer)
+            }
+        }
+    }
+}
+
+/// A single action emitted by a pointer input source.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PointerAction<'a> {
+    PointerMove {
+        duration: u64,
+        origin: PointerOrigin<'a>,
+        x: i64,
+        y: i64,
+    },
+    PointerDown {
+        button: u64,
+    },
+    PointerUp {
+        button: u64,
+    },
+    Pause {
+        duration: u64,
+    },
+}
+
+/// A single action emitted by a key input source.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum KeyAction {
+    KeyDown { value: char },
+    KeyUp { value: char },
+    Pause { duration: u64 },
+}
+
+/// A single action emitted by a wheel input source.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WheelAction {
+    Scroll {
+        x: i64,
+        y: i64,
+        #[serde(rename = "deltaX")]
+        delta_x: i64,
+        #[serde(rename = "deltaY")]
+        delta_y: i64,
+        duration: u64,
+    },
+    Pause {
+        duration: u64,
+    },
+}
+
+/// A single action emitted by a `none` input source.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NoneAction {
+    Pause { duration: u64 },
+}
+
+/// Pointer-source-specific parameters nested under `ActionSequence::Pointer`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PointerParameters {
+    #[serde(rename = "pointerType")]
+    pub pointer_type: PointerType,
+}
+
+/// One input source's list of actions, as sent in the W3C actions payload.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ActionSequence<'a> {
+    Pointer {
+        id: String,
+        parameters: PointerParameters,
+        actions: Vec<PointerAction<'a>>,
+    },
+    Key {
+        id: String,
+        actions: Vec<KeyAction>,
+    },
+    Wheel {
+        id: String,
+        actions: Vec<WheelAction>,
+    },
+    None {
+        id: String,
+        actions: Vec<NoneAction>,
+    },
+}
+
+/// The payload for `Command::PerformActions`.
+///
+/// Built from a list of `ActionSequence`s, one per input source, and
+/// serializes to the W3C actions payload. A `From<serde_json::Value>` escape
+/// hatch is retained for callers that already have a raw payload to send.
+#[derive(Debug, Clone)]
+pub enum Actions<'a> {
+    Sequences(Vec<ActionSequence<'a>>),
+    Raw(serde_json::Value),
+}
+
+impl<'a> Actions<'a> {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Actions::Sequences(sequences) => json!(sequences),
+            Actions::Raw(value) => value.clone(),
+        }
+    }
+}
+
+impl<'a> From<Vec<ActionSequence<'a>>> for Actions<'a> {
+    fn from(sequences: Vec<ActionSequence<'a>>) -> Self {
+        Actions::Sequences(sequences)
+    }
+}
+
+impl<'a> From<serde_json::Value> for Actions<'a> {
     fn from(value: serde_json::Value) -> Self {
-        Actions(value)
+        Actions::Raw(value)
     }
 }
 
@@ -74,8 +241,194 @@ impl<'a> By<'a> {
     }
 }
 
+/// The type of browsing context to request from `Command::NewWindow`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowType {
+    Tab,
+    Window,
+}
+
+/// Page orientation for `Command::Print`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrintOrientation {
+    Portrait,
+    Landscape,
+}
+
+/// Page size, in centimeters, for `Command::Print`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrintPage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+}
+
+/// Page margins, in centimeters, for `Command::Print`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrintMargin {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bottom: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub left: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub right: Option<f64>,
+}
+
+/// Parameters for `Command::Print`, matching the W3C print endpoint.
+///
+/// Every field is optional so that the driver falls back to its own default
+/// when a field is omitted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrintParameters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orientation: Option<PrintOrientation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<PrintPage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub margin: Option<PrintMargin>,
+    #[serde(rename = "pageRanges", skip_serializing_if = "Option::is_none")]
+    pub page_ranges: Option<Vec<String>>,
+    #[serde(rename = "shrinkToFit", skip_serializing_if = "Option::is_none")]
+    pub shrink_to_fit: Option<bool>,
+}
+
+/// Unique identifier for a virtual authenticator, as returned by
+/// `Command::AddVirtualAuthenticator`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AuthenticatorId(String);
+
+impl fmt::Display for AuthenticatorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<String> for AuthenticatorId {
+    fn from(id: String) -> Self {
+        AuthenticatorId(id)
+    }
+}
+
+/// The CTAP protocol a virtual authenticator speaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthenticatorProtocol {
+    Ctap2,
+    U2f,
+}
+
+/// The transport a virtual authenticator emulates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthenticatorTransport {
+    Usb,
+    Nfc,
+    Ble,
+    Internal,
+}
+
+/// Configuration for `Command::AddVirtualAuthenticator`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthenticatorConfig {
+    pub protocol: AuthenticatorProtocol,
+    pub transport: AuthenticatorTransport,
+    #[serde(rename = "hasResidentKey")]
+    pub has_resident_key: bool,
+    #[serde(rename = "hasUserVerification")]
+    pub has_user_verification: bool,
+    #[serde(rename = "isUserConsenting")]
+    pub is_user_consenting: bool,
+    #[serde(rename = "isUserVerified")]
+    pub is_user_verified: bool,
+}
+
+/// A WebAuthn credential to register on a virtual authenticator via
+/// `Command::AddCredential`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialParameters {
+    #[serde(rename = "credentialId")]
+    pub credential_id: String,
+    #[serde(rename = "isResidentCredential")]
+    pub is_resident_credential: bool,
+    #[serde(rename = "rpId")]
+    pub rp_id: String,
+    #[serde(rename = "privateKey")]
+    pub private_key: String,
+    #[serde(rename = "userHandle", skip_serializing_if = "Option::is_none")]
+    pub user_handle: Option<String>,
+    #[serde(rename = "signCount")]
+    pub sign_count: u32,
+}
+
+/// Implemented by vendor-specific (`moz:`, `goog:`, etc.) extension commands.
+///
+/// This gives users a supported path to drive browser-specific endpoints —
+/// Firefox context switching, Chromium cast/network-conditions, and so on —
+/// without forking the crate. Implementors supply the request method, the
+/// session-relative URL suffix (appended after `/session/{session}`), and an
+/// optional JSON body.
+pub trait WebDriverExtensionCommand: fmt::Debug {
+    fn method(&self) -> RequestMethod;
+    fn suffix(&self) -> String;
+    fn parameters_json(&self) -> Option<Value>;
+}
+
+/// Capabilities negotiation payload for `Command::NewSession`.
+///
+/// Mirrors the W3C `alwaysMatch`/`firstMatch` split: `always_match` holds
+/// capabilities that must be present in every configuration the driver
+/// considers, while `first_match` lists alternative capability sets in
+/// priority order for the driver to try. A plain capabilities map lowers to
+/// `always_match` with an empty `first_match` via `From<&Value>`/`From<Value>`,
+/// so callers keep their existing single-map *behavior* by adding a `.into()`
+/// at the `Command::NewSession` call site. `legacy_caps` is sent as-is under
+/// the legacy `desiredCapabilities` field for drivers that only understand
+/// the old JSON Wire Protocol shape, separately from the W3C-converted
+/// `always_match`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpecNewSessionParameters {
+    pub always_match: Value,
+    pub first_match: Vec<Value>,
+    pub legacy_caps: Value,
+}
+
+impl SpecNewSessionParameters {
+    pub fn new(always_match: Value, first_match: Vec<Value>) -> Self {
+        SpecNewSessionParameters {
+            legacy_caps: always_match.clone(),
+            always_match,
+            first_match,
+        }
+    }
+}
+
+impl From<&Value> for SpecNewSessionParameters {
+    fn from(caps: &Value) -> Self {
+        SpecNewSessionParameters {
+            always_match: make_w3c_caps(caps),
+            first_match: Vec::new(),
+            legacy_caps: caps.clone(),
+        }
+    }
+}
+
+impl From<Value> for SpecNewSessionParameters {
+    fn from(caps: Value) -> Self {
+        SpecNewSessionParameters::from(&caps)
+    }
+}
+
 pub enum Command<'a> {
-    NewSession(&'a Value),
+    NewSession(SpecNewSessionParameters),
     DeleteSession,
     Status,
     GetTimeouts,
@@ -90,6 +443,7 @@ pub enum Command<'a> {
     CloseWindow,
     SwitchToWindow(&'a WindowHandle),
     GetWindowHandles,
+    NewWindow(WindowType),
     SwitchToFrameDefault,
     SwitchToFrameNumber(u16),
     SwitchToFrameElement(&'a ElementId),
@@ -104,6 +458,9 @@ pub enum Command<'a> {
     FindElements(By<'a>),
     FindElementFromElement(&'a ElementId, By<'a>),
     FindElementsFromElement(&'a ElementId, By<'a>),
+    GetElementShadowRoot(&'a ElementId),
+    FindElementFromShadowRoot(&'a ShadowId, By<'a>),
+    FindElementsFromShadowRoot(&'a ShadowId, By<'a>),
     IsElementSelected(&'a ElementId),
     GetElementAttribute(&'a ElementId, String),
     GetElementProperty(&'a ElementId, String),
@@ -123,7 +480,7 @@ pub enum Command<'a> {
     AddCookie(Cookie),
     DeleteCookie(&'a str),
     DeleteAllCookies,
-    PerformActions(Actions),
+    PerformActions(Actions<'a>),
     ReleaseActions,
     DismissAlert,
     AcceptAlert,
@@ -131,18 +488,28 @@ pub enum Command<'a> {
     SendAlertText(TypingData),
     TakeScreenshot,
     TakeElementScreenshot(&'a ElementId),
+    Print(PrintParameters),
+    AddVirtualAuthenticator(AuthenticatorConfig),
+    RemoveVirtualAuthenticator(&'a AuthenticatorId),
+    AddCredential(&'a AuthenticatorId, CredentialParameters),
+    GetCredentials(&'a AuthenticatorId),
+    RemoveCredential(&'a AuthenticatorId, &'a str),
+    RemoveAllCredentials(&'a AuthenticatorId),
+    SetUserVerified(&'a AuthenticatorId, bool),
+    Extension(Box<dyn WebDriverExtensionCommand + 'a>),
 }
 
 impl<'a> Command<'a> {
     pub fn format_request(&self, session_id: &SessionId) -> RequestData {
         match self {
-            Command::NewSession(caps) => {
-                let w3c_caps = make_w3c_caps(&caps);
-                RequestData::new(RequestMethod::Post, "/session").add_body(json!({
-                    "capabilities": w3c_caps,
-                    "desiredCapabilities": caps
-                }))
-            }
+            Command::NewSession(params) => RequestData::new(RequestMethod::Post, "/session")
+                .add_body(json!({
+                    "capabilities": {
+                        "alwaysMatch": params.always_match,
+                        "firstMatch": params.first_match,
+                    },
+                    "desiredCapabilities": params.legacy_caps
+                })),
             Command::DeleteSession => {
                 RequestData::new(RequestMethod::Delete, format!("/session/{}", session_id))
             }
@@ -197,6 +564,11 @@ impl<'a> Command<'a> {
                 RequestMethod::Get,
                 format!("/session/{}/window/handles", session_id),
             ),
+            Command::NewWindow(window_type) => RequestData::new(
+                RequestMethod::Post,
+                format!("/session/{}/window/new", session_id),
+            )
+            .add_body(json!({ "type": window_type })),
             Command::SwitchToFrameDefault => RequestData::new(
                 RequestMethod::Post,
                 format!("/session/{}/frame", session_id),
@@ -280,6 +652,26 @@ impl<'a> Command<'a> {
                 )
                 .add_body(json!({"using": selector, "value": value}))
             }
+            Command::GetElementShadowRoot(element_id) => RequestData::new(
+                RequestMethod::Get,
+                format!("/session/{}/element/{}/shadow", session_id, element_id),
+            ),
+            Command::FindElementFromShadowRoot(shadow_id, by) => {
+                let (selector, value) = by.get_w3c_selector();
+                RequestData::new(
+                    RequestMethod::Post,
+                    format!("/session/{}/shadow/{}/element", session_id, shadow_id),
+                )
+                .add_body(json!({"using": selector, "value": value}))
+            }
+            Command::FindElementsFromShadowRoot(shadow_id, by) => {
+                let (selector, value) = by.get_w3c_selector();
+                RequestData::new(
+                    RequestMethod::Post,
+                    format!("/session/{}/shadow/{}/elements", session_id, shadow_id),
+                )
+                .add_body(json!({"using": selector, "value": value}))
+            }
             Command::IsElementSelected(element_id) => RequestData::new(
                 RequestMethod::Get,
                 format!("/session/{}/element/{}/selected", session_id, element_id),
@@ -375,7 +767,7 @@ impl<'a> Command<'a> {
                 RequestMethod::Post,
                 format!("/session/{}/actions", session_id),
             )
-            .add_body(json!({"actions": actions.0})),
+            .add_body(json!({"actions": actions.to_json()})),
             Command::ReleaseActions => RequestData::new(
                 RequestMethod::Delete,
                 format!("/session/{}/actions", session_id),
@@ -409,6 +801,301 @@ impl<'a> Command<'a> {
                 RequestMethod::Get,
                 format!("/session/{}/element/{}/screenshot", session_id, element_id),
             ),
+            Command::Print(print_parameters) => RequestData::new(
+                RequestMethod::Post,
+                format!("/session/{}/print", session_id),
+            )
+            .add_body(json!(print_parameters)),
+            Command::AddVirtualAuthenticator(config) => RequestData::new(
+                RequestMethod::Post,
+                format!("/session/{}/webauthn/authenticator", session_id),
+            )
+            .add_body(json!(config)),
+            Command::RemoveVirtualAuthenticator(authenticator_id) => RequestData::new(
+                RequestMethod::Delete,
+                format!(
+                    "/session/{}/webauthn/authenticator/{}",
+                    session_id, authenticator_id
+                ),
+            ),
+            Command::AddCredential(authenticator_id, credential) => RequestData::new(
+                RequestMethod::Post,
+                format!(
+                    "/session/{}/webauthn/authenticator/{}/credential",
+                    session_id, authenticator_id
+                ),
+            )
+            .add_body(json!(credential)),
+            Command::GetCredentials(authenticator_id) => RequestData::new(
+                RequestMethod::Get,
+                format!(
+                    "/session/{}/webauthn/authenticator/{}/credentials",
+                    session_id, authenticator_id
+                ),
+            ),
+            Command::RemoveCredential(authenticator_id, credential_id) => RequestData::new(
+                RequestMethod::Delete,
+                format!(
+                    "/session/{}/webauthn/authenticator/{}/credentials/{}",
+                    session_id, authenticator_id, credential_id
+                ),
+            ),
+            Command::RemoveAllCredentials(authenticator_id) => RequestData::new(
+                RequestMethod::Delete,
+                format!(
+                    "/session/{}/webauthn/authenticator/{}/credentials",
+                    session_id, authenticator_id
+                ),
+            ),
+            Command::SetUserVerified(authenticator_id, verified) => RequestData::new(
+                RequestMethod::Post,
+                format!(
+                    "/session/{}/webauthn/authenticator/{}/uv",
+                    session_id, authenticator_id
+                ),
+            )
+            .add_body(json!({ "isUserVerified": verified })),
+            Command::Extension(command) => {
+                let request = RequestData::new(
+                    command.method(),
+                    format!("/session/{}{}", session_id, command.suffix()),
+                );
+                match command.parameters_json() {
+                    Some(body) => request.add_body(body),
+                    None => request,
+                }
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::connection_common::unwrap_new_window;
+
+    #[test]
+    fn get_element_shadow_root_targets_element() {
+        let session_id = SessionId::from("test-session".to_string());
+        let element_id = ElementId::from("element-1".to_string());
+
+        let request = Command::GetElementShadowRoot(&element_id).format_request(&session_id);
+
+        assert_eq!(
+            request.url,
+            "/session/test-session/element/element-1/shadow"
+        );
+    }
+
+    #[test]
+    fn find_element_from_shadow_root_sends_selector_body() {
+        let session_id = SessionId::from("test-session".to_string());
+        let shadow_id = ShadowId::from("shadow-1".to_string());
+
+        let request = Command::FindElementFromShadowRoot(&shadow_id, By::Css(".widget"))
+            .format_request(&session_id);
+
+        assert_eq!(request.url, "/session/test-session/shadow/shadow-1/element");
+        assert_eq!(
+            request.body,
+            Some(json!({"using": "css selector", "value": ".widget"}))
+        );
+    }
+
+    #[test]
+    fn find_elements_from_shadow_root_sends_selector_body() {
+        let session_id = SessionId::from("test-session".to_string());
+        let shadow_id = ShadowId::from("shadow-1".to_string());
+
+        let request = Command::FindElementsFromShadowRoot(&shadow_id, By::Css(".widget"))
+            .format_request(&session_id);
+
+        assert_eq!(
+            request.url,
+            "/session/test-session/shadow/shadow-1/elements"
+        );
+        assert_eq!(
+            request.body,
+            Some(json!({"using": "css selector", "value": ".widget"}))
+        );
+    }
+
+    #[test]
+    fn new_window_sends_window_type() {
+        let session_id = SessionId::from("test-session".to_string());
+
+        let request = Command::NewWindow(WindowType::Tab).format_request(&session_id);
+
+        assert_eq!(request.url, "/session/test-session/window/new");
+        assert_eq!(request.body, Some(json!({"type": "tab"})));
+    }
+
+    #[test]
+    fn unwrap_new_window_round_trips_handle_and_type() {
+        let value = json!({"handle": "window-1", "type": "window"});
+
+        let (handle, window_type) = unwrap_new_window(&value).unwrap();
+
+        assert_eq!(handle, WindowHandle::from("window-1".to_string()));
+        assert_eq!(window_type, WindowType::Window);
+    }
+
+    #[test]
+    fn print_request_omits_unset_fields() {
+        let session_id = SessionId::from("test-session".to_string());
+        let params = PrintParameters {
+            orientation: Some(PrintOrientation::Landscape),
+            background: Some(true),
+            ..Default::default()
+        };
+
+        let request = Command::Print(params).format_request(&session_id);
+
+        assert_eq!(request.url, "/session/test-session/print");
+        assert_eq!(
+            request.body,
+            Some(json!({"orientation": "landscape", "background": true}))
+        );
+    }
+
+    #[test]
+    fn add_virtual_authenticator_serializes_config() {
+        let session_id = SessionId::from("test-session".to_string());
+        let config = AuthenticatorConfig {
+            protocol: AuthenticatorProtocol::Ctap2,
+            transport: AuthenticatorTransport::Internal,
+            has_resident_key: true,
+            has_user_verification: true,
+            is_user_consenting: true,
+            is_user_verified: true,
+        };
+
+        let request = Command::AddVirtualAuthenticator(config).format_request(&session_id);
+
+        assert_eq!(request.url, "/session/test-session/webauthn/authenticator");
+        assert_eq!(
+            request.body,
+            Some(json!({
+                "protocol": "ctap2",
+                "transport": "internal",
+                "hasResidentKey": true,
+                "hasUserVerification": true,
+                "isUserConsenting": true,
+                "isUserVerified": true
+            }))
+        );
+    }
+
+    #[test]
+    fn remove_credential_targets_authenticator_and_credential_id() {
+        let session_id = SessionId::from("test-session".to_string());
+        let authenticator_id = AuthenticatorId::from("auth-1".to_string());
+
+        let request =
+            Command::RemoveCredential(&authenticator_id, "cred-1").format_request(&session_id);
+
+        assert_eq!(
+            request.url,
+            "/session/test-session/webauthn/authenticator/auth-1/credentials/cred-1"
+        );
+    }
+
+    #[derive(Debug)]
+    struct MozContext {
+        context: &'static str,
+    }
+
+    impl WebDriverExtensionCommand for MozContext {
+        fn method(&self) -> RequestMethod {
+            RequestMethod::Post
+        }
+
+        fn suffix(&self) -> String {
+            "/moz/context".to_string()
+        }
+
+        fn parameters_json(&self) -> Option<Value> {
+            Some(json!({ "context": self.context }))
+        }
+    }
+
+    #[derive(Debug)]
+    struct NoBodyExtension;
+
+    impl WebDriverExtensionCommand for NoBodyExtension {
+        fn method(&self) -> RequestMethod {
+            RequestMethod::Get
+        }
+
+        fn suffix(&self) -> String {
+            "/moz/screenshot/full".to_string()
+        }
+
+        fn parameters_json(&self) -> Option<Value> {
+            None
+        }
+    }
+
+    #[test]
+    fn extension_command_delegates_method_suffix_and_body() {
+        let session_id = SessionId::from("test-session".to_string());
+        let command = Box::new(MozContext { context: "chrome" });
+
+        let request = Command::Extension(command).format_request(&session_id);
+
+        assert!(matches!(request.method, RequestMethod::Post));
+        assert_eq!(request.url, "/session/test-session/moz/context");
+        assert_eq!(request.body, Some(json!({"context": "chrome"})));
+    }
+
+    #[test]
+    fn extension_command_omits_body_when_none() {
+        let session_id = SessionId::from("test-session".to_string());
+        let command = Box::new(NoBodyExtension);
+
+        let request = Command::Extension(command).format_request(&session_id);
+
+        assert_eq!(request.url, "/session/test-session/moz/screenshot/full");
+        assert_eq!(request.body, None);
+    }
+
+    #[test]
+    fn perform_actions_nests_pointer_type_under_parameters() {
+        let session_id = SessionId::from("test-session".to_string());
+        let sequence = ActionSequence::Pointer {
+            id: "mouse".to_string(),
+            parameters: PointerParameters {
+                pointer_type: PointerType::Mouse,
+            },
+            actions: vec![PointerAction::PointerDown { button: 0 }],
+        };
+
+        let request =
+            Command::PerformActions(Actions::from(vec![sequence])).format_request(&session_id);
+
+        assert_eq!(
+            request.body,
+            Some(json!({
+                "actions": [{
+                    "type": "pointer",
+                    "id": "mouse",
+                    "parameters": {"pointerType": "mouse"},
+                    "actions": [{"type": "pointerDown", "button": 0}]
+                }]
+            }))
+        );
+    }
+
+    #[test]
+    fn new_session_keeps_legacy_caps_unconverted() {
+        let session_id = SessionId::from("test-session".to_string());
+        let caps = json!({"browserName": "firefox"});
+        let params = SpecNewSessionParameters::from(&caps);
+
+        let request = Command::NewSession(params).format_request(&session_id);
+
+        let body = request.body.unwrap();
+        assert_eq!(body["desiredCapabilities"], caps);
+        assert_eq!(body["capabilities"]["firstMatch"], json!([]));
+    }
+}
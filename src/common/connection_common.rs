@@ -1,10 +1,11 @@
+use crate::common::{command::WindowType, types::WindowHandle};
 use crate::error::{RemoteConnectionError, WebDriverResult};
 use base64::encode;
 use reqwest::{
     self,
     header::{HeaderMap, ACCEPT, AUTHORIZATION, CONNECTION, CONTENT_TYPE, USER_AGENT},
 };
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize};
 use urlparse::urlparse;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -44,3 +45,16 @@ where
     let v: Vec<T> = serde_json::from_value(value.clone())?;
     Ok(v)
 }
+
+#[derive(Debug, Deserialize)]
+struct NewWindowResponse {
+    handle: WindowHandle,
+    #[serde(rename = "type")]
+    window_type: WindowType,
+}
+
+/// Unwrap the response from `Command::NewWindow` into its handle/type pair.
+pub fn unwrap_new_window(value: &serde_json::Value) -> WebDriverResult<(WindowHandle, WindowType)> {
+    let response: NewWindowResponse = serde_json::from_value(value.clone())?;
+    Ok((response.handle, response.window_type))
+}